@@ -30,6 +30,7 @@
 use anyhow::{Context, Result};
 use rand::{seq::SliceRandom, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -47,17 +48,29 @@ pub struct PackageInfo {
     /// DPL specification version (v0.1 or v0.2)
     #[serde(default = "default_dpl_version")]
     pub dpl_version: String,
+    /// Whether `dustpkg package` embeds `dustpkg.lock` in the
+    /// distributable archive so downstream consumers rebuild
+    /// against the exact same locked dependency set.
+    #[serde(default = "default_publish_lockfile")]
+    pub publish_lockfile: bool,
 }
 
 fn default_dpl_version() -> String {
     "0.2".to_string()
 }
 
+fn default_publish_lockfile() -> bool {
+    true
+}
+
 /// Manifest structure for `Dust.toml`.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Manifest {
     pub package: PackageInfo,
-    /// Map of dependency names to version requirements.
+    /// Map of dependency names to semver version *requirements*
+    /// (e.g. `"1.0.0"` or `"^1.2"`), not concrete versions.  The
+    /// concrete version actually locked for each dependency is
+    /// recorded in `LockedDep::version`.
     #[serde(default)]
     pub dependencies: HashMap<String, String>,
 }
@@ -82,14 +95,183 @@ impl Manifest {
     }
 }
 
+/// One version of a package published in a registry `Index`, along
+/// with the version requirements it places on its own dependencies.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub version: String,
+    /// Map of dependency name to version requirement for this
+    /// specific version of the package.
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    /// Path (relative to the index file) to the vendored source
+    /// archive or directory for this version, used to compute a
+    /// content-addressed checksum. `None` for entries that have no
+    /// source available to hash yet.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl IndexEntry {
+    /// Resolve this entry's `source` path against the directory the
+    /// index file was loaded from.
+    fn source_path(&self, index_base_dir: &Path) -> Option<PathBuf> {
+        self.source.as_ref().map(|source| index_base_dir.join(source))
+    }
+}
+
+/// An offline registry index used to resolve dependency requirements
+/// into concrete versions.  A real `dustpkg` registry would fetch
+/// this data from a remote server; for now resolution works entirely
+/// from a local index file (keyed by package name) so that builds
+/// stay reproducible without network access.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Index {
+    /// Map of package name to every version published for it.
+    #[serde(default)]
+    pub packages: HashMap<String, Vec<IndexEntry>>,
+    /// Directory the index file was loaded from, used to resolve
+    /// each entry's relative `source` path. Not part of the index's
+    /// on-disk representation.
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+impl Index {
+    /// Load an index from `path`.  A missing index file is treated
+    /// as an empty index rather than an error, since not every
+    /// project needs one (e.g. a package with no dependencies).
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read registry index at {}", path.display()))?;
+        let mut index: Self = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse registry index at {}", path.display()))?;
+        index.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(index)
+    }
+
+    /// Every published version of `name` that parses as semver.
+    fn versions_for(&self, name: &str) -> Vec<Version> {
+        self.packages
+            .get(name)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| Version::parse(&entry.version).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The highest published version of `name` that satisfies *every*
+    /// requirement in `reqs` (Cargo-style maximal version selection).
+    fn resolve_version<'a>(
+        &self,
+        name: &str,
+        reqs: impl Iterator<Item = &'a VersionReq>,
+    ) -> Option<Version> {
+        let reqs: Vec<&VersionReq> = reqs.collect();
+        self.versions_for(name)
+            .into_iter()
+            .filter(|version| reqs.iter().all(|req| req.matches(version)))
+            .max()
+    }
+
+    /// The index entry for a specific published `name`@`version`.
+    fn entry_for(&self, name: &str, version: &Version) -> Option<&IndexEntry> {
+        self.packages.get(name)?.iter().find(|entry| {
+            Version::parse(&entry.version)
+                .map(|v| &v == version)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Checksum to record in the lock file for `name`@`version`: a
+    /// content-address over the real vendored source when the index
+    /// entry has one, otherwise a checksum of the literal
+    /// `name@version` string (used for entries with no source yet,
+    /// e.g. in tests).
+    fn checksum_for(&self, name: &str, version: &Version) -> Result<String> {
+        if let Some(entry) = self.entry_for(name, version) {
+            if let Some(source_path) = entry.source_path(&self.base_dir) {
+                return hash_source(&source_path);
+            }
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}@{}", name, version).as_bytes());
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Compute a SHA-256 digest over the real on-disk source for a
+/// dependency. A single file is hashed directly; a directory is
+/// walked recursively in sorted order, hashing each file's path
+/// (relative to `path`) followed by its bytes, so the digest is
+/// stable regardless of filesystem iteration order.
+fn hash_source(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    if path.is_dir() {
+        let mut files = list_files(path)?;
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(path).unwrap_or(&file);
+            hasher.update(relative.to_string_lossy().as_bytes());
+            let bytes = fs::read(&file)
+                .with_context(|| format!("failed to read source file {}", file.display()))?;
+            hasher.update(&bytes);
+        }
+    } else {
+        let bytes = fs::read(path)
+            .with_context(|| format!("failed to read source archive at {}", path.display()))?;
+        hasher.update(&bytes);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Recursively list every file under `dir`.
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read source directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(list_files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// A requirement placed on a package by either the manifest or one
+/// of its (transitive) dependencies.  Kept around purely so that
+/// conflict errors can name both requesters.
+#[derive(Debug, Clone)]
+struct Requirement {
+    spec: String,
+    requested_by: String,
+}
+
 /// Locked dependency entry in `dustpkg.lock`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct LockedDep {
     pub name: String,
+    /// Concrete version selected for this dependency, i.e. the
+    /// highest published version satisfying the manifest's
+    /// requirement for `name`.
     pub version: String,
-    /// SHA‑256 checksum of the dependency specification (name@version).
+    /// SHA‑256 checksum of the dependency's real vendored source, as
+    /// produced by [`hash_source`], or of the literal `name@version`
+    /// string when the index entry has no source. Re-checked by
+    /// `dustpkg verify` and by `build_package`.
     pub checksum: String,
-    /// Source location of the dependency (not used yet).
+    /// Path to the vendored source this checksum was computed over,
+    /// relative to the registry index (or a synthetic `registry/...`
+    /// placeholder when the index entry has no vendored source).
     pub source: String,
 }
 
@@ -124,46 +306,177 @@ impl Lockfile {
     }
 }
 
+/// Path to the registry index that sits alongside a manifest.
+fn index_path_for(manifest_path: &Path) -> PathBuf {
+    manifest_path.with_file_name("registry-index.toml")
+}
+
 /// Resolve a manifest into a lock file, deterministically ordering
 /// dependencies using the provided seed.  If `seed` is `None`, the
 /// dependencies are sorted alphabetically; otherwise the list is
-/// shuffled using the seed.  Each locked dependency records a
-/// checksum computed over its `name@version` pair.  The seed is
-/// recorded in the resulting lock file.
-pub fn resolve(manifest: &Manifest, seed: Option<u64>) -> Lockfile {
-    let mut deps: Vec<(String, String)> = manifest
-        .dependencies
-        .iter()
-        .map(|(name, version)| (name.clone(), version.clone()))
-        .collect();
+/// shuffled using the seed.
+///
+/// Each dependency requirement is parsed as a `VersionReq` and
+/// matched against the versions published for that package in
+/// `index`, selecting the maximum satisfying version (Cargo-style
+/// maximal version selection).  An error naming the package and the
+/// requirement is returned if no published version satisfies it.
+/// Each locked dependency records a checksum of its vendored source
+/// (or, when its index entry has no source, of its resolved
+/// `name@version` pair), and the seed is recorded in the resulting
+/// lock file.
+pub fn resolve(manifest: &Manifest, index: &Index, seed: Option<u64>) -> Result<Lockfile> {
+    let resolved = resolve_transitive(manifest, index)?;
+
+    let mut deps: Vec<(String, Version)> = resolved.into_iter().collect();
+    // `resolved` iterates in no fixed order; sort first so a seeded
+    // shuffle always starts from the same list.
+    deps.sort_by(|a, b| a.0.cmp(&b.0));
     if let Some(seed_val) = seed {
         let mut rng = ChaCha8Rng::seed_from_u64(seed_val);
         deps.shuffle(&mut rng);
     } else {
         deps.sort_by(|a, b| a.0.cmp(&b.0));
     }
-    let locked_deps: Vec<LockedDep> = deps
-        .into_iter()
-        .map(|(name, version)| {
-            let checksum = {
-                let mut hasher = Sha256::new();
-                hasher.update(format!("{}@{}", name, version).as_bytes());
-                let result = hasher.finalize();
-                hex::encode(result)
-            };
-            LockedDep {
-                name: name.clone(),
-                version: version.clone(),
-                checksum,
-                source: format!("registry/{}-{}", name, version),
-            }
-        })
-        .collect();
-    Lockfile {
+    let mut locked_deps = Vec::with_capacity(deps.len());
+    for (name, version) in deps {
+        let checksum = index.checksum_for(&name, &version)?;
+        let source = index
+            .entry_for(&name, &version)
+            .and_then(|entry| entry.source.clone())
+            .unwrap_or_else(|| format!("registry/{}-{}", name, version));
+        locked_deps.push(LockedDep {
+            name,
+            version: version.to_string(),
+            checksum,
+            source,
+        });
+    }
+    Ok(Lockfile {
         package: manifest.package.clone(),
         dependencies: locked_deps,
         seed,
+    })
+}
+
+/// Resolve every requirement reachable from the manifest's direct
+/// dependencies, transitively, against `index`.
+///
+/// The dependency frontier is expanded breadth-first: each package is
+/// visited once per new requirement placed on it, requirements are
+/// accumulated, and a package's own dependencies (as declared for the
+/// version currently selected for it) are pushed onto the frontier
+/// the first time it is resolved or whenever a tighter requirement
+/// changes its selected version. Packages already fully resolved for
+/// the accumulated requirement set are not re-expanded. A dependency
+/// cycle (a package transitively depending on itself) or a version
+/// conflict (two requirements on the same package that no single
+/// published version satisfies) is reported as an error naming the
+/// requesters involved.
+fn resolve_transitive(manifest: &Manifest, index: &Index) -> Result<HashMap<String, Version>> {
+    let mut requirements: HashMap<String, Vec<Requirement>> = HashMap::new();
+    let mut resolved: HashMap<String, Version> = HashMap::new();
+    // Each frontier entry carries the full chain of package names
+    // that led to it (empty for a direct manifest dependency), not
+    // just its immediate parent. A global "first discoverer" map
+    // can't tell a real cycle apart from two direct dependencies that
+    // merely happen to share a transitive dependency, since both
+    // would be recorded as introduced by "<manifest>"; carrying the
+    // actual path per in-flight requirement avoids that.
+    let mut frontier: std::collections::VecDeque<(String, String, Vec<String>)> = manifest
+        .dependencies
+        .iter()
+        .map(|(name, requirement)| (name.clone(), requirement.clone(), Vec::new()))
+        .collect();
+
+    while let Some((name, spec, path)) = frontier.pop_front() {
+        if path.contains(&name) {
+            anyhow::bail!(
+                "dependency cycle detected: '{}' transitively depends on itself via '{}'",
+                name,
+                path.last().unwrap()
+            );
+        }
+        let requested_by = path
+            .last()
+            .cloned()
+            .unwrap_or_else(|| "<manifest>".to_string());
+
+        let reqs = requirements.entry(name.clone()).or_default();
+        reqs.push(Requirement {
+            spec: spec.clone(),
+            requested_by: requested_by.clone(),
+        });
+        let parsed: Vec<VersionReq> = reqs
+            .iter()
+            .map(|r| {
+                VersionReq::parse(&r.spec).with_context(|| {
+                    format!(
+                        "invalid version requirement '{}' for dependency '{}' (requested by '{}')",
+                        r.spec, name, r.requested_by
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let version = match index.resolve_version(&name, parsed.iter()) {
+            Some(version) => version,
+            None if reqs.len() == 1 => {
+                anyhow::bail!(
+                    "no version of '{}' satisfies requirement '{}'",
+                    name,
+                    reqs[0].spec
+                );
+            }
+            None => {
+                let first = &reqs[0];
+                let last = reqs.last().unwrap();
+                anyhow::bail!(
+                    "version conflict for dependency '{}': '{}' requires {}, '{}' requires {}",
+                    name,
+                    first.requested_by,
+                    first.spec,
+                    last.requested_by,
+                    last.spec
+                );
+            }
+        };
+
+        let changed = resolved.get(&name) != Some(&version);
+        resolved.insert(name.clone(), version.clone());
+
+        if changed {
+            if let Some(entry) = index.entry_for(&name, &version) {
+                let mut child_path = path.clone();
+                child_path.push(name.clone());
+                for (dep_name, dep_req) in &entry.dependencies {
+                    frontier.push_back((dep_name.clone(), dep_req.clone(), child_path.clone()));
+                }
+            }
+        }
     }
+
+    // A package whose selected version did not change is not expanded
+    // again, so a cycle entered from two direct dependencies never
+    // meets itself on a path above. Peel off packages nothing else
+    // depends on; whatever remains lies on or below a cycle.
+    let mut remaining: HashMap<&String, Vec<&String>> = resolved
+        .iter()
+        .map(|(name, version)| {
+            let deps = index.entry_for(name, version).map(|e| e.dependencies.keys().collect::<Vec<_>>());
+            (name, deps.unwrap_or_default())
+        })
+        .collect();
+    while let Some(free) = remaining.keys().copied().find(|n| !remaining.values().flatten().any(|d| d == n)) {
+        remaining.remove(free);
+    }
+    if !remaining.is_empty() {
+        let mut stuck: Vec<&str> = remaining.keys().map(|name| name.as_str()).collect();
+        stuck.sort();
+        anyhow::bail!("dependency cycle detected among: {}", stuck.join(", "));
+    }
+
+    Ok(resolved)
 }
 
 /// Initialise a new package in the given directory.  This creates a
@@ -183,6 +496,7 @@ pub fn init_package(dir: &Path) -> Result<()> {
             name: name.to_string(),
             version: "0.1.0".to_string(),
             dpl_version: "0.2".to_string(),
+            publish_lockfile: true,
         },
         dependencies: HashMap::new(),
     };
@@ -206,7 +520,8 @@ pub fn add_dependency(
         .dependencies
         .insert(dep_name.to_string(), dep_version.to_string());
     manifest.save(manifest_path)?;
-    let lock = resolve(&manifest, seed);
+    let index = Index::load(&index_path_for(manifest_path))?;
+    let lock = resolve(&manifest, &index, seed)?;
     let lock_path = manifest_path.with_file_name("dustpkg.lock");
     lock.save(&lock_path)?;
     println!(
@@ -236,7 +551,8 @@ pub fn add_stdlib_dependencies(manifest_path: &Path, seed: Option<u64>) -> Resul
     }
 
     manifest.save(manifest_path)?;
-    let lock = resolve(&manifest, seed);
+    let index = Index::load(&index_path_for(manifest_path))?;
+    let lock = resolve(&manifest, &index, seed)?;
     let lock_path = manifest_path.with_file_name("dustpkg.lock");
     lock.save(&lock_path)?;
     println!("Added standard library dependencies (dustlib, dustlib_k)");
@@ -247,7 +563,8 @@ pub fn add_stdlib_dependencies(manifest_path: &Path, seed: Option<u64>) -> Resul
 /// modify the manifest.  The seed controls dependency ordering.
 pub fn update_lock(manifest_path: &Path, seed: Option<u64>) -> Result<()> {
     let manifest = Manifest::load(manifest_path)?;
-    let lock = resolve(&manifest, seed);
+    let index = Index::load(&index_path_for(manifest_path))?;
+    let lock = resolve(&manifest, &index, seed)?;
     let lock_path = manifest_path.with_file_name("dustpkg.lock");
     lock.save(&lock_path)?;
     println!("Updated {}", lock_path.display());
@@ -256,33 +573,370 @@ pub fn update_lock(manifest_path: &Path, seed: Option<u64>) -> Result<()> {
 
 /// Build the package by verifying the manifest and lock file are
 /// consistent.  This function currently only checks that every
-/// dependency in the manifest is present in the lock file and that
-/// their versions match.  In a real implementation this would
-/// perform compilation, caching and vendoring.  A seed may be
+/// dependency requirement in the manifest is still satisfied by the
+/// version recorded in the lock file, after first re-hashing the
+/// sources of an existing lock file against its recorded checksums.
+/// In a real implementation this
+/// would perform compilation, caching and vendoring.  A seed may be
 /// supplied to re-resolve the lock file before building.
 pub fn build_package(manifest_path: &Path, seed: Option<u64>) -> Result<()> {
-    // Optionally update the lock file to ensure it matches the manifest
+    let lock_path = manifest_path.with_file_name("dustpkg.lock");
+    let index = Index::load(&index_path_for(manifest_path))?;
+
+    // Verify the checksums recorded in the *existing* lock file
+    // before re-resolving: `update_lock` below recomputes every
+    // checksum from whatever is currently on disk, so checking
+    // afterwards would only ever compare freshly-hashed data against
+    // itself and could never catch a tampered source.
+    if lock_path.exists() {
+        let existing_lock = Lockfile::load(&lock_path)?;
+        verify_checksums(&index, &existing_lock)?;
+    }
+
+    // Update the lock file to ensure it matches the manifest
     update_lock(manifest_path, seed)?;
     let manifest = Manifest::load(manifest_path)?;
-    let lock_path = manifest_path.with_file_name("dustpkg.lock");
     let lock = Lockfile::load(&lock_path)?;
-    // Check that each manifest dependency appears in the lock file with the same version
-    for (name, version) in &manifest.dependencies {
-        if let Some(entry) = lock.dependencies.iter().find(|dep| &dep.name == name) {
-            if &entry.version != version {
-                anyhow::bail!(
-                    "version mismatch for dependency '{}': manifest {} vs lock {}",
-                    name,
-                    version,
-                    entry.version
-                );
-            }
-        } else {
-            anyhow::bail!("dependency '{}' missing from lock file", name);
-        }
-    }
+    check_consistency(&manifest, &lock)?;
     // At this point a real implementation would compile or prepare
     // artifacts.  We simply output a success message.
     println!("Build successful. All dependencies resolved deterministically.");
     Ok(())
 }
+
+/// Check that every manifest requirement is still satisfied by the
+/// version recorded for it in `lock`.
+fn check_consistency(manifest: &Manifest, lock: &Lockfile) -> Result<()> {
+    for (name, requirement) in &manifest.dependencies {
+        let req = VersionReq::parse(requirement).with_context(|| {
+            format!(
+                "invalid version requirement '{}' for dependency '{}'",
+                requirement, name
+            )
+        })?;
+        let entry = lock
+            .dependencies
+            .iter()
+            .find(|dep| &dep.name == name)
+            .ok_or_else(|| anyhow::anyhow!("dependency '{}' missing from lock file", name))?;
+        let locked_version = Version::parse(&entry.version).with_context(|| {
+            format!(
+                "malformed version '{}' for dependency '{}' in lock file",
+                entry.version, name
+            )
+        })?;
+        if !req.matches(&locked_version) {
+            anyhow::bail!(
+                "locked version {} of '{}' no longer satisfies requirement '{}'",
+                locked_version,
+                name,
+                req
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Re-hash the on-disk source for every dependency recorded in
+/// `lock` and fail loudly if any digest no longer matches, so the
+/// lock file's "reproducible build" promise is actually enforced.
+fn verify_checksums(index: &Index, lock: &Lockfile) -> Result<()> {
+    for dep in &lock.dependencies {
+        let version = Version::parse(&dep.version).with_context(|| {
+            format!(
+                "malformed version '{}' for dependency '{}' in lock file",
+                dep.version, dep.name
+            )
+        })?;
+        let actual = index.checksum_for(&dep.name, &version)?;
+        if actual != dep.checksum {
+            anyhow::bail!(
+                "checksum mismatch for '{}' {}: lock file records {}, on-disk source hashes to {}",
+                dep.name,
+                dep.version,
+                dep.checksum,
+                actual
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Re-hash the on-disk source for every dependency in `dustpkg.lock`
+/// against the registry index and fail if any digest has drifted.
+/// Unlike `build_package`, this does not re-resolve the lock file
+/// first; it verifies exactly what is currently locked.
+pub fn verify_package(manifest_path: &Path) -> Result<()> {
+    let lock_path = manifest_path.with_file_name("dustpkg.lock");
+    let lock = Lockfile::load(&lock_path)?;
+    let index = Index::load(&index_path_for(manifest_path))?;
+    verify_checksums(&index, &lock)?;
+    println!(
+        "Verified {} dependency checksum(s) against the registry index.",
+        lock.dependencies.len()
+    );
+    Ok(())
+}
+
+/// Whether a newer version of a dependency is semver-compatible with
+/// the one currently resolved, or a breaking (major) upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeKind {
+    /// Newer version shares the current major version (or `0.x`
+    /// minor, per semver's pre-1.0 convention).
+    Compatible,
+    /// Newer version is a breaking upgrade.
+    Major,
+}
+
+impl std::fmt::Display for UpgradeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpgradeKind::Compatible => write!(f, "Compatible"),
+            UpgradeKind::Major => write!(f, "Major"),
+        }
+    }
+}
+
+/// One row of `dustpkg outdated` output: a direct dependency whose
+/// currently resolved version is behind the newest one published in
+/// the registry index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutdatedEntry {
+    pub name: String,
+    pub current: String,
+    pub latest: String,
+    pub kind: UpgradeKind,
+}
+
+/// Compare each direct dependency's version recorded in
+/// `dustpkg.lock` against the newest version published for it in the
+/// registry index. Only the real lock file is read here; nothing is
+/// re-resolved or written, so the user's lock file is never mutated
+/// while probing for upgrades.
+pub fn outdated(manifest_path: &Path) -> Result<Vec<OutdatedEntry>> {
+    let manifest = Manifest::load(manifest_path)?;
+    let lock_path = manifest_path.with_file_name("dustpkg.lock");
+    let lock = Lockfile::load(&lock_path)
+        .context("no lock file found; run `dustpkg update` or `dustpkg build` first")?;
+    let index = Index::load(&index_path_for(manifest_path))?;
+
+    let mut entries = Vec::new();
+    for name in manifest.dependencies.keys() {
+        let locked = lock
+            .dependencies
+            .iter()
+            .find(|dep| &dep.name == name)
+            .ok_or_else(|| anyhow::anyhow!("dependency '{}' missing from lock file", name))?;
+        let current_version = Version::parse(&locked.version).with_context(|| {
+            format!("malformed version '{}' for '{}' in lock file", locked.version, name)
+        })?;
+        let latest = index
+            .versions_for(name)
+            .into_iter()
+            .max()
+            .ok_or_else(|| anyhow::anyhow!("package '{}' not found in registry index", name))?;
+        if latest > current_version {
+            let kind = if is_major_upgrade(&current_version, &latest) {
+                UpgradeKind::Major
+            } else {
+                UpgradeKind::Compatible
+            };
+            entries.push(OutdatedEntry {
+                name: name.clone(),
+                current: current_version.to_string(),
+                latest: latest.to_string(),
+                kind,
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Whether bumping from `current` to `latest` is a breaking upgrade
+/// under semver's compatibility rules: a major bump always breaks,
+/// and so does a minor bump while the major version is still `0`,
+/// since pre-1.0 releases treat the minor component as
+/// the de-facto breaking boundary.
+fn is_major_upgrade(current: &Version, latest: &Version) -> bool {
+    if current.major != latest.major {
+        return true;
+    }
+    current.major == 0 && current.minor != latest.minor
+}
+
+/// Bundle the current package for distribution. `Dust.toml` is
+/// always included; `dustpkg.lock` is included as well whenever
+/// `publish_lockfile` is set in the manifest, so that whoever
+/// consumes the archive rebuilds against the exact locked dependency
+/// set this package was tested with. Refuses to run if the manifest
+/// and lock file are inconsistent (reusing the same check
+/// `build_package` performs) rather than silently packaging a stale
+/// lock. Returns the path to the produced package directory.
+pub fn package(manifest_path: &Path) -> Result<PathBuf> {
+    let manifest = Manifest::load(manifest_path)?;
+    let lock_path = manifest_path.with_file_name("dustpkg.lock");
+    let lock = Lockfile::load(&lock_path)
+        .context("no lock file found; run `dustpkg build` or `dustpkg update` first")?;
+    let index = Index::load(&index_path_for(manifest_path))?;
+    check_consistency(&manifest, &lock)?;
+    verify_checksums(&index, &lock)?;
+
+    let dist_dir = manifest_path
+        .with_file_name("target")
+        .join("package")
+        .join(format!("{}-{}", manifest.package.name, manifest.package.version));
+    fs::create_dir_all(&dist_dir)
+        .with_context(|| format!("failed to create package directory {}", dist_dir.display()))?;
+    fs::copy(manifest_path, dist_dir.join("Dust.toml"))
+        .context("failed to bundle Dust.toml into the package")?;
+    if manifest.package.publish_lockfile {
+        fs::copy(&lock_path, dist_dir.join("dustpkg.lock"))
+            .context("failed to bundle dustpkg.lock into the package")?;
+    }
+    println!(
+        "Packaged {}-{} into {}",
+        manifest.package.name,
+        manifest.package.version,
+        dist_dir.display()
+    );
+    Ok(dist_dir)
+}
+
+/// One binary tracked by `dustpkg install`: which package and
+/// version put it there, so a later install can tell a routine
+/// upgrade apart from clobbering an unrelated package.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InstalledBinary {
+    pub package: String,
+    pub version: String,
+    pub path: PathBuf,
+}
+
+/// Tracking metadata for every binary `dustpkg install` has placed
+/// under an install root, persisted as `installed.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct InstallTracker {
+    #[serde(default)]
+    pub binaries: Vec<InstalledBinary>,
+}
+
+impl InstallTracker {
+    /// Load tracking metadata from `path`. A missing file means
+    /// nothing has been tracked yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read install metadata at {}", path.display()))?;
+        let tracker: Self = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse install metadata at {}", path.display()))?;
+        Ok(tracker)
+    }
+
+    /// Save tracking metadata to `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let toml_string =
+            toml::to_string_pretty(self).context("failed to serialize install metadata to TOML")?;
+        fs::write(path, toml_string)
+            .with_context(|| format!("failed to write install metadata at {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Resolve, build and install a single package's binary into
+/// `root/bin`, recording which package/version put it there in
+/// `root/installed.toml` (unless `track` is `false`).
+///
+/// Re-installing the same package upgrades its binary when the
+/// resolved version differs from what's tracked. Installing over a
+/// path owned by a *different* package, or over an untracked file,
+/// is refused unless `force` is set. If writing the binary fails
+/// partway through, the partial file is removed rather than left
+/// behind for a later build to trip over.
+pub fn install_package(
+    root: &Path,
+    index_path: &Path,
+    name: &str,
+    version: &str,
+    force: bool,
+    track: bool,
+) -> Result<()> {
+    let index = Index::load(index_path)?;
+    let req = VersionReq::parse(version)
+        .with_context(|| format!("invalid version requirement '{}' for '{}'", version, name))?;
+    let resolved = index
+        .resolve_version(name, std::iter::once(&req))
+        .ok_or_else(|| anyhow::anyhow!("no version of '{}' satisfies requirement '{}'", name, req))?;
+
+    // "Build" the package before installing it: make sure its source
+    // actually hashes to something, so a broken vendor entry is
+    // caught before any binary is written.
+    index.checksum_for(name, &resolved)?;
+
+    let bin_dir = root.join("bin");
+    fs::create_dir_all(&bin_dir)
+        .with_context(|| format!("failed to create install directory {}", bin_dir.display()))?;
+    let bin_path = bin_dir.join(name);
+
+    let tracker_path = root.join("installed.toml");
+    let mut tracker = InstallTracker::load(&tracker_path)?;
+    let existing = tracker.binaries.iter().find(|b| b.path == bin_path).cloned();
+
+    match &existing {
+        Some(prior) if prior.package != name && !force => {
+            anyhow::bail!(
+                "refusing to overwrite '{}', installed by '{}' {} (use --force)",
+                bin_path.display(),
+                prior.package,
+                prior.version
+            );
+        }
+        Some(prior) if prior.package == name && prior.version == resolved.to_string() && !force => {
+            println!("'{}' {} is already installed", name, resolved);
+            return Ok(());
+        }
+        None if bin_path.exists() && !force => {
+            anyhow::bail!(
+                "refusing to overwrite untracked file at {} (use --force)",
+                bin_path.display()
+            );
+        }
+        _ => {}
+    }
+
+    if let Err(err) = write_stub_binary(&bin_path, name, &resolved) {
+        let _ = fs::remove_file(&bin_path);
+        return Err(err);
+    }
+
+    let had_entry = tracker.binaries.iter().any(|b| b.path == bin_path);
+    tracker.binaries.retain(|b| b.path != bin_path);
+    if track {
+        tracker.binaries.push(InstalledBinary {
+            package: name.to_string(),
+            version: resolved.to_string(),
+            path: bin_path.clone(),
+        });
+        tracker.save(&tracker_path)?;
+    } else if had_entry {
+        // Drop the now-stale record rather than let it claim a
+        // version that no longer matches what's on disk.
+        tracker.save(&tracker_path)?;
+    }
+
+    println!("Installed '{}' {} to {}", name, resolved, bin_path.display());
+    Ok(())
+}
+
+/// Write the (stubbed) binary for `name`@`version`. A real
+/// implementation would compile and link the package; `dustpkg`
+/// doesn't do that yet, so this just records what would have been
+/// installed.
+fn write_stub_binary(path: &Path, name: &str, version: &Version) -> Result<()> {
+    let contents = format!("#!/usr/bin/env dustvm\n# {} {}\n", name, version);
+    fs::write(path, contents)
+        .with_context(|| format!("failed to write binary at {}", path.display()))
+}
@@ -7,7 +7,10 @@
 //! delegated to the library functions.
 
 use clap::{Parser, Subcommand};
-use dustpkg::{add_dependency, build_package, init_package, update_lock};
+use dustpkg::{
+    add_dependency, build_package, init_package, install_package, outdated, package, update_lock,
+    verify_package,
+};
 use std::path::PathBuf;
 
 /// A deterministic package manager for the Dust programming language.
@@ -46,6 +49,34 @@ enum Commands {
         #[arg(long)]
         seed: Option<u64>,
     },
+    /// Re-hash the on-disk source for every locked dependency and
+    /// fail if any digest no longer matches `dustpkg.lock`.
+    Verify,
+    /// List dependencies that have a newer version available in the
+    /// registry index than the one currently resolved.
+    Outdated {
+        /// Exit with a non-zero status if any dependency is outdated,
+        /// so this can gate CI.
+        #[arg(long)]
+        exit_code: bool,
+    },
+    /// Bundle `Dust.toml` (and, by default, `dustpkg.lock`) into a
+    /// distributable package directory.
+    Package,
+    /// Resolve, build and install a package's binary.
+    Install {
+        /// Name of the package to install.
+        name: String,
+        /// Version requirement to install.
+        version: String,
+        /// Overwrite an existing binary even if it belongs to a
+        /// different package or isn't tracked.
+        #[arg(long)]
+        force: bool,
+        /// Install without recording tracking metadata.
+        #[arg(long = "no-track")]
+        no_track: bool,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -74,6 +105,48 @@ fn main() -> anyhow::Result<()> {
             }
             build_package(&manifest_path, seed)?;
         }
+        Commands::Verify => {
+            if !manifest_path.exists() {
+                anyhow::bail!("Dust.toml not found in {}", cwd.display());
+            }
+            verify_package(&manifest_path)?;
+        }
+        Commands::Outdated { exit_code } => {
+            if !manifest_path.exists() {
+                anyhow::bail!("Dust.toml not found in {}", cwd.display());
+            }
+            let entries = outdated(&manifest_path)?;
+            if entries.is_empty() {
+                println!("All dependencies are up to date.");
+            } else {
+                println!("{:<20}{:<12}{:<12}{:<10}", "Name", "Current", "Latest", "Kind");
+                for entry in &entries {
+                    println!(
+                        "{:<20}{:<12}{:<12}{:<10}",
+                        entry.name, entry.current, entry.latest, entry.kind
+                    );
+                }
+            }
+            if exit_code && !entries.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Package => {
+            if !manifest_path.exists() {
+                anyhow::bail!("Dust.toml not found in {}", cwd.display());
+            }
+            package(&manifest_path)?;
+        }
+        Commands::Install {
+            name,
+            version,
+            force,
+            no_track,
+        } => {
+            let root = cwd.join("target").join("install");
+            let index_path = cwd.join("registry-index.toml");
+            install_package(&root, &index_path, &name, &version, force, !no_track)?;
+        }
     }
     Ok(())
 }
\ No newline at end of file
@@ -11,6 +11,21 @@ fn run_dustpkg(args: &[&str], dir: &Path) -> assert_cmd::assert::Assert {
     cmd.assert()
 }
 
+/// Write a minimal registry index next to the manifest so that
+/// `resolve` has candidate versions to select from.
+fn write_index(dir: &Path, packages: &[(&str, &[&str])]) {
+    let mut toml = String::new();
+    for (name, versions) in packages {
+        let entries = versions
+            .iter()
+            .map(|v| format!("{{ version = \"{}\" }}", v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        toml.push_str(&format!("packages.{} = [{}]\n", name, entries));
+    }
+    fs::write(dir.join("registry-index.toml"), toml).unwrap();
+}
+
 #[test]
 fn init_creates_manifest() {
     let tmp = TempDir::new().unwrap();
@@ -27,6 +42,7 @@ fn init_creates_manifest() {
 fn add_dependency_and_lock() {
     let tmp = TempDir::new().unwrap();
     run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
     run_dustpkg(&["add", "serde", "1.0.0"], tmp.path()).success();
     // After adding, we should have both manifest and lock file
     let manifest_path = tmp.path().join("Dust.toml");
@@ -36,16 +52,273 @@ fn add_dependency_and_lock() {
     // Check manifest includes dependency
     let manifest = fs::read_to_string(&manifest_path).unwrap();
     assert!(manifest.contains("serde"), "serde should be listed in dependencies");
-    // Check lock file includes dependency and checksum
+    // Check lock file includes the resolved version and checksum
     let lock = fs::read_to_string(&lock_path).unwrap();
     assert!(lock.contains("name = \"serde\""), "lock should include serde");
+    assert!(lock.contains("version = \"1.0.0\""), "lock should include resolved version");
     assert!(lock.contains("checksum"), "lock should include checksum");
 }
 
+#[test]
+fn add_dependency_picks_maximal_satisfying_version() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0", "1.2.0", "1.3.5", "2.0.0"])]);
+    // "^1.2" should resolve to the highest 1.x release, not 2.0.0
+    run_dustpkg(&["add", "serde", "^1.2"], tmp.path()).success();
+    let lock = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
+    assert!(lock.contains("version = \"1.3.5\""), "should select the maximal satisfying version");
+}
+
+#[test]
+fn add_dependency_fails_when_no_version_satisfies_requirement() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
+    run_dustpkg(&["add", "serde", "^2.0"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("no version of 'serde' satisfies requirement '^2.0'"));
+}
+
+#[test]
+fn add_dependency_resolves_transitively() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    // `app` depends on `a`, which in turn depends on `b`.
+    fs::write(
+        tmp.path().join("registry-index.toml"),
+        r#"
+        packages.a = [{ version = "1.0.0", dependencies = { b = "1.0.0" } }]
+        packages.b = [{ version = "1.0.0" }, { version = "1.1.0" }]
+        "#,
+    )
+    .unwrap();
+    run_dustpkg(&["add", "a", "1.0.0"], tmp.path()).success();
+    let lock = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
+    assert!(lock.contains("name = \"a\""), "lock should include the direct dependency");
+    assert!(lock.contains("name = \"b\""), "lock should include the transitive dependency");
+    assert!(lock.contains("version = \"1.1.0\""), "transitive dep should pick the maximal satisfying version");
+}
+
+#[test]
+fn add_dependency_reports_version_conflict() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    // `a` requires b ^1.0, `c` requires b ^2.0: unsatisfiable by one version.
+    fs::write(
+        tmp.path().join("registry-index.toml"),
+        r#"
+        packages.a = [{ version = "1.0.0", dependencies = { b = "^1.0" } }]
+        packages.c = [{ version = "1.0.0", dependencies = { b = "^2.0" } }]
+        packages.b = [{ version = "1.5.0" }, { version = "2.0.0" }]
+        "#,
+    )
+    .unwrap();
+    run_dustpkg(&["add", "a", "1.0.0"], tmp.path()).success();
+    run_dustpkg(&["add", "c", "1.0.0"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("version conflict for dependency 'b'"));
+}
+
+#[test]
+fn verify_detects_checksum_mismatch() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    fs::write(tmp.path().join("serde-1.0.0.src"), b"original source bytes").unwrap();
+    fs::write(
+        tmp.path().join("registry-index.toml"),
+        r#"packages.serde = [{ version = "1.0.0", source = "serde-1.0.0.src" }]"#,
+    )
+    .unwrap();
+    run_dustpkg(&["add", "serde", "1.0.0"], tmp.path()).success();
+    run_dustpkg(&["verify"], tmp.path()).success();
+
+    // Mutate the vendored source after locking; verify should now fail.
+    fs::write(tmp.path().join("serde-1.0.0.src"), b"tampered source bytes").unwrap();
+    run_dustpkg(&["verify"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("checksum mismatch for 'serde' 1.0.0"));
+}
+
+#[test]
+fn build_detects_tampered_source_before_rewriting_the_lock() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    fs::write(tmp.path().join("serde-1.0.0.src"), b"original source bytes").unwrap();
+    fs::write(
+        tmp.path().join("registry-index.toml"),
+        r#"packages.serde = [{ version = "1.0.0", source = "serde-1.0.0.src" }]"#,
+    )
+    .unwrap();
+    run_dustpkg(&["add", "serde", "1.0.0"], tmp.path()).success();
+    run_dustpkg(&["build"], tmp.path()).success();
+    let lock_before = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
+
+    // Tamper with the vendored source after locking; build should
+    // refuse to proceed instead of silently re-hashing the tampered
+    // bytes into the lock file.
+    fs::write(tmp.path().join("serde-1.0.0.src"), b"tampered source bytes").unwrap();
+    run_dustpkg(&["build"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("checksum mismatch for 'serde' 1.0.0"));
+
+    let lock_after = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
+    assert_eq!(lock_before, lock_after, "build must not rewrite the lock file over a checksum mismatch");
+}
+
+#[test]
+fn outdated_reports_behind_dependencies_without_touching_the_lock() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
+    run_dustpkg(&["add", "serde", "^1.0"], tmp.path()).success();
+    let lock_before = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
+
+    // A newer, semver-compatible release appears in the registry.
+    write_index(tmp.path(), &[("serde", &["1.0.0", "1.4.0"])]);
+    run_dustpkg(&["outdated"], tmp.path())
+        .success()
+        .stdout(predicate::str::contains("serde").and(predicate::str::contains("Compatible")));
+
+    let lock_after = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
+    assert_eq!(lock_before, lock_after, "outdated must not mutate dustpkg.lock");
+}
+
+#[test]
+fn outdated_treats_a_zero_x_minor_bump_as_major() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("foo", &["0.1.0"])]);
+    run_dustpkg(&["add", "foo", "^0.1"], tmp.path()).success();
+    write_index(tmp.path(), &[("foo", &["0.1.0", "0.5.0"])]);
+    run_dustpkg(&["outdated"], tmp.path())
+        .success()
+        .stdout(predicate::str::contains("foo").and(predicate::str::contains("Major")));
+}
+
+#[test]
+fn outdated_exit_code_flag_fails_when_behind() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
+    run_dustpkg(&["add", "serde", "^1.0"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0", "2.0.0"])]);
+    run_dustpkg(&["outdated", "--exit-code"], tmp.path()).failure();
+}
+
+#[test]
+fn package_bundles_manifest_and_lockfile() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
+    run_dustpkg(&["add", "serde", "1.0.0"], tmp.path()).success();
+    run_dustpkg(&["package"], tmp.path()).success();
+
+    let pkg_name = tmp.path().file_name().unwrap().to_str().unwrap();
+    let dist_dir = tmp
+        .path()
+        .join("target")
+        .join("package")
+        .join(format!("{}-0.1.0", pkg_name));
+    assert!(dist_dir.join("Dust.toml").exists());
+    assert!(dist_dir.join("dustpkg.lock").exists());
+}
+
+#[test]
+fn package_refuses_when_lock_is_inconsistent() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
+    run_dustpkg(&["add", "serde", "1.0.0"], tmp.path()).success();
+    // Edit the manifest to require a version the lock file doesn't have.
+    let manifest_path = tmp.path().join("Dust.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+    fs::write(&manifest_path, manifest.replace("1.0.0", "^2.0")).unwrap();
+    run_dustpkg(&["package"], tmp.path()).failure();
+}
+
+#[test]
+fn install_writes_binary_and_tracking_metadata() {
+    let tmp = TempDir::new().unwrap();
+    write_index(tmp.path(), &[("greet", &["1.0.0"])]);
+    run_dustpkg(&["install", "greet", "1.0.0"], tmp.path()).success();
+
+    let bin_path = tmp.path().join("target/install/bin/greet");
+    assert!(bin_path.exists(), "binary should be installed");
+    let tracked = fs::read_to_string(tmp.path().join("target/install/installed.toml")).unwrap();
+    assert!(tracked.contains("package = \"greet\""));
+    assert!(tracked.contains("version = \"1.0.0\""));
+}
+
+#[test]
+fn install_upgrades_when_version_differs() {
+    let tmp = TempDir::new().unwrap();
+    write_index(tmp.path(), &[("greet", &["1.0.0", "1.1.0"])]);
+    run_dustpkg(&["install", "greet", "1.0.0"], tmp.path()).success();
+    run_dustpkg(&["install", "greet", "^1.0"], tmp.path()).success();
+
+    let tracked = fs::read_to_string(tmp.path().join("target/install/installed.toml")).unwrap();
+    assert!(tracked.contains("version = \"1.1.0\""), "should have upgraded to 1.1.0");
+}
+
+#[test]
+fn install_refuses_to_clobber_an_untracked_binary_without_force() {
+    let tmp = TempDir::new().unwrap();
+    write_index(tmp.path(), &[("greet", &["1.0.0"])]);
+    run_dustpkg(&["install", "greet", "1.0.0"], tmp.path()).success();
+
+    // Rename the binary path's owner by installing a different package at the same bin path
+    // is not directly possible (names differ), so instead simulate an untracked clobber:
+    // drop the tracking file but keep the binary, then reinstall without --force.
+    fs::remove_file(tmp.path().join("target/install/installed.toml")).unwrap();
+    run_dustpkg(&["install", "greet", "1.0.0"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("refusing to overwrite untracked file"));
+
+    run_dustpkg(&["install", "greet", "1.0.0", "--force"], tmp.path()).success();
+}
+
+#[test]
+fn install_no_track_skips_metadata() {
+    let tmp = TempDir::new().unwrap();
+    write_index(tmp.path(), &[("greet", &["1.0.0"])]);
+    run_dustpkg(&["install", "greet", "1.0.0", "--no-track"], tmp.path()).success();
+
+    assert!(tmp.path().join("target/install/bin/greet").exists());
+    assert!(!tmp.path().join("target/install/installed.toml").exists());
+}
+
+#[test]
+fn add_dependency_detects_cycle_between_two_direct_dependencies() {
+    let tmp = TempDir::new().unwrap();
+    run_dustpkg(&["init"], tmp.path()).success();
+    // `b` and `c` mutually require each other. Both also end up as
+    // direct manifest dependencies once both are added, which used to
+    // fool cycle detection into thinking each was merely "introduced
+    // by the manifest" instead of by the other.
+    fs::write(
+        tmp.path().join("registry-index.toml"),
+        r#"
+        packages.b = [{ version = "1.0.0", dependencies = { c = "1.0.0" } }]
+        packages.c = [{ version = "1.0.0", dependencies = { b = "1.0.0" } }]
+        "#,
+    )
+    .unwrap();
+    run_dustpkg(&["add", "b", "1.0.0"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("dependency cycle detected"));
+    // The failed add still saved `b` to the manifest; adding `c` makes
+    // both of them direct dependencies.
+    run_dustpkg(&["add", "c", "1.0.0"], tmp.path())
+        .failure()
+        .stderr(predicate::str::contains("dependency cycle detected"));
+}
+
 #[test]
 fn build_after_add() {
     let tmp = TempDir::new().unwrap();
     run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("serde", &["1.0.0"])]);
     run_dustpkg(&["add", "serde", "1.0.0"], tmp.path()).success();
     // build should succeed
     run_dustpkg(&["build"], tmp.path()).success();
@@ -55,6 +328,7 @@ fn build_after_add() {
 fn update_with_seed_produces_different_order() {
     let tmp = TempDir::new().unwrap();
     run_dustpkg(&["init"], tmp.path()).success();
+    write_index(tmp.path(), &[("a", &["0.1.0"]), ("b", &["0.2.0"])]);
     // Add two dependencies
     run_dustpkg(&["add", "a", "0.1.0"], tmp.path()).success();
     run_dustpkg(&["add", "b", "0.2.0"], tmp.path()).success();
@@ -66,4 +340,4 @@ fn update_with_seed_produces_different_order() {
     let lock_seed42 = fs::read_to_string(tmp.path().join("dustpkg.lock")).unwrap();
     // If seed influences ordering, the two lock contents should differ
     assert_ne!(lock_seed0, lock_seed42, "different seeds should produce different lock ordering");
-}
\ No newline at end of file
+}